@@ -0,0 +1,450 @@
+use getopts::Options;
+
+use fontcode::error::ParseError;
+use fontcode::read::ReadScope;
+use fontcode::tables::bitmap::{BitmapLocaTable, IndexSubTable, SbixStrike, SbixTable};
+use fontcode::tables::{MaxpTable, OffsetTable, OpenTypeFile, OpenTypeFont};
+use fontcode::tag;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    Message(&'static str),
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optopt(
+        "e",
+        "export",
+        "export every bitmap glyph into DIR instead of listing strikes",
+        "DIR",
+    );
+    opts.optflag("h", "help", "print this help menu");
+
+    let matches = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => panic!(f.to_string()),
+    };
+
+    if matches.opt_present("h") || matches.free.is_empty() {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+
+    let filename = &matches.free[0];
+    let buffer = read_file(filename)?;
+    let fontfile = ReadScope::new(&buffer).read::<OpenTypeFile>()?;
+    let ttf = match fontfile.font {
+        OpenTypeFont::Single(ttf) => ttf,
+        OpenTypeFont::Collection(_) => return Err(Error::Message("TTC not supported")),
+    };
+
+    let strikes = read_strikes(fontfile.scope, &ttf)?;
+    if strikes.is_empty() {
+        println!("no embedded bitmap/color tables found (EBLC/EBDT, CBLC/CBDT, sbix)");
+        return Ok(());
+    }
+
+    match matches.opt_str("e") {
+        Some(dir) => export_strikes(&dir, &strikes)?,
+        None => list_strikes(&strikes),
+    }
+
+    Ok(())
+}
+
+fn print_usage(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options] FONTFILE ", program);
+    eprint!("{}", opts.usage(&brief));
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, io::Error> {
+    let mut file = File::open(path)?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// A decoded glyph image, ready to export as a standalone PNG. `Png`
+/// covers the CBDT PNG-wrapped formats (17-19), which are already
+/// complete PNGs, and `sbix` glyph data, which is passed through as
+/// stored without checking its graphic type (so a JPEG or PDF `sbix`
+/// glyph is also listed and exported as PNG). `Mono` covers the EBDT monochrome formats (1, 2,
+/// 5, 6, 7 - formats 8 and 9 are composite glyph references, not bitmaps,
+/// and aren't handled here), reconstructed into a plain 1-bit-per-pixel
+/// row-major bitmap we encode to PNG ourselves.
+enum BitmapData {
+    Png(Vec<u8>),
+    Mono {
+        width: u32,
+        height: u32,
+        // Row-major, 1 bit per pixel, rows padded to a byte boundary -
+        // the same layout the byte-aligned EBDT formats store on disk.
+        rows: Vec<u8>,
+    },
+}
+
+impl BitmapData {
+    /// `bit_aligned` distinguishes the byte-aligned monochrome formats (1,
+    /// 6), whose rows are padded out to a byte boundary on disk, from the
+    /// bit-aligned formats (2, 5, 7), which pack every row back-to-back
+    /// with no per-row padding and so need unpacking bit-by-bit before we
+    /// can treat them as the row-major, byte-padded layout `encode_mono_png`
+    /// expects.
+    fn reconstruct(
+        index_subtable: &IndexSubTable,
+        image: &[u8],
+        bit_aligned: bool,
+    ) -> Result<BitmapData, Error> {
+        let metrics = index_subtable.glyph_metrics(image)?;
+        let width = u32::from(metrics.width);
+        let height = u32::from(metrics.height);
+        let rows = if bit_aligned {
+            let packed_len = (width as usize * height as usize + 7) / 8;
+            let packed = image
+                .get(metrics.bitmap_offset..metrics.bitmap_offset + packed_len)
+                .ok_or(ParseError::BadOffset)?;
+            unpack_bit_aligned(packed, width, height)
+        } else {
+            let row_bytes = ((width + 7) / 8) as usize;
+            image
+                .get(metrics.bitmap_offset..metrics.bitmap_offset + row_bytes * height as usize)
+                .ok_or(ParseError::BadOffset)?
+                .to_vec()
+        };
+        Ok(BitmapData::Mono {
+            width,
+            height,
+            rows,
+        })
+    }
+
+    fn format_name(&self) -> String {
+        match self {
+            BitmapData::Png(data) => format!("PNG, {} bytes", data.len()),
+            BitmapData::Mono { width, height, .. } => {
+                format!("1-bit monochrome, {}x{}", width, height)
+            }
+        }
+    }
+
+    fn to_png(&self) -> Result<Vec<u8>, Error> {
+        match self {
+            BitmapData::Png(data) => Ok(data.clone()),
+            BitmapData::Mono {
+                width,
+                height,
+                rows,
+            } => encode_mono_png(*width, *height, rows),
+        }
+    }
+}
+
+/// Unpack a bit-aligned (formats 2, 5, 7) monochrome image - every row
+/// packed back-to-back with no padding - into the row-major,
+/// byte-padded-per-row layout the byte-aligned formats already use, so
+/// both can share `encode_mono_png`.
+fn unpack_bit_aligned(packed: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let row_bytes = ((width + 7) / 8) as usize;
+    let mut rows = vec![0u8; row_bytes * height as usize];
+    let mut bit_index = 0usize;
+    for row in 0..height as usize {
+        for col in 0..width as usize {
+            let byte = packed[bit_index / 8];
+            let set = byte & (0x80 >> (bit_index % 8)) != 0;
+            if set {
+                rows[row * row_bytes + col / 8] |= 0x80 >> (col % 8);
+            }
+            bit_index += 1;
+        }
+    }
+    rows
+}
+
+/// Encode a 1-bit-per-pixel bitmap as a minimal grayscale PNG: one IHDR,
+/// one zlib-compressed IDAT (each scanline prefixed with filter type 0),
+/// and an IEND, each length-prefixed and CRC32-suffixed per the PNG spec.
+fn encode_mono_png(width: u32, height: u32, rows: &[u8]) -> Result<Vec<u8>, Error> {
+    let row_bytes = ((width + 7) / 8) as usize;
+
+    // Expand the packed 1-bit-per-pixel rows into one grayscale byte per
+    // pixel (set bit -> white, clear bit -> black), each row prefixed
+    // with the PNG "None" filter type byte.
+    let mut idat_raw = Vec::with_capacity(height as usize * (width as usize + 1));
+    for row in rows.chunks(row_bytes) {
+        idat_raw.push(0);
+        for col in 0..width {
+            let byte = row[(col / 8) as usize];
+            let set = byte & (0x80 >> (col % 8)) != 0;
+            idat_raw.push(if set { 0xFF } else { 0x00 });
+        }
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&idat_raw)?;
+    let compressed = encoder.finish()?;
+
+    let mut png = Vec::new();
+    png.extend_from_slice(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+    write_png_chunk(&mut png, b"IHDR", &{
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.push(8); // bit depth
+        ihdr.push(0); // color type: grayscale
+        ihdr.push(0); // compression method
+        ihdr.push(0); // filter method
+        ihdr.push(0); // interlace method
+        ihdr
+    });
+    write_png_chunk(&mut png, b"IDAT", &compressed);
+    write_png_chunk(&mut png, b"IEND", &[]);
+
+    Ok(png)
+}
+
+fn write_png_chunk(out: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let mut crc_input = Vec::with_capacity(4 + data.len());
+    crc_input.extend_from_slice(chunk_type);
+    crc_input.extend_from_slice(data);
+    out.extend_from_slice(chunk_type);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&crc32(&crc_input).to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+struct Strike {
+    source: &'static str,
+    // u16, not u8: EBLC/CBLC strikes are u8 ppem, but sbix strikes are
+    // keyed by a u16 ppem and commonly exceed 255.
+    ppem_x: u16,
+    ppem_y: u16,
+    bit_depth: u8,
+    glyphs: Vec<(u16, BitmapData)>,
+}
+
+/// Collect every embedded-bitmap strike the font defines: `EBLC`/`EBDT`
+/// (monochrome/grayscale glyphs), `CBLC`/`CBDT` (PNG-wrapped color
+/// glyphs), and Apple's `sbix` (PNG/JPEG/PDF color glyphs keyed directly
+/// by ppem rather than an index sub-table).
+fn read_strikes(scope: ReadScope, ttf: &OffsetTable) -> Result<Vec<Strike>, Error> {
+    let mut strikes = Vec::new();
+    strikes.extend(read_loca_strikes(
+        scope,
+        ttf,
+        tag::EBLC,
+        tag::EBDT,
+        "EBLC/EBDT",
+    )?);
+    strikes.extend(read_loca_strikes(
+        scope,
+        ttf,
+        tag::CBLC,
+        tag::CBDT,
+        "CBLC/CBDT",
+    )?);
+    strikes.extend(read_sbix_strikes(scope, ttf)?);
+    Ok(strikes)
+}
+
+fn read_loca_strikes(
+    scope: ReadScope,
+    ttf: &OffsetTable,
+    loca_tag: u32,
+    data_tag: u32,
+    source: &'static str,
+) -> Result<Vec<Strike>, Error> {
+    let loca_data = match ttf.read_table(scope, loca_tag)? {
+        Some(data) => data,
+        None => return Ok(Vec::new()),
+    };
+    let bitmap_data = match ttf.read_table(scope, data_tag)? {
+        Some(data) => data,
+        None => return Ok(Vec::new()),
+    };
+
+    let loca_table = loca_data.read::<BitmapLocaTable>()?;
+    let mut strikes = Vec::with_capacity(loca_table.bitmap_sizes.len());
+
+    for bitmap_size in &loca_table.bitmap_sizes {
+        let mut glyphs = Vec::new();
+        for index_subtable in &bitmap_size.index_subtables {
+            // Formats 1-5 differ in how they store per-glyph offsets and
+            // metrics; `glyph_offsets()` abstracts over that.
+            for (glyph_id, offset, length) in index_subtable.glyph_offsets() {
+                let image = bitmap_data
+                    .data()
+                    .get(offset..offset + length)
+                    .ok_or(ParseError::BadOffset)?;
+                if let Some(data) = decode_glyph_image(index_subtable, image)? {
+                    glyphs.push((glyph_id, data));
+                }
+            }
+        }
+        strikes.push(Strike {
+            source,
+            ppem_x: u16::from(bitmap_size.ppem_x),
+            ppem_y: u16::from(bitmap_size.ppem_y),
+            bit_depth: bitmap_size.bit_depth,
+            glyphs,
+        });
+    }
+
+    Ok(strikes)
+}
+
+/// Decode one glyph's image, or return `None` to have the caller omit it
+/// from the strike - an unsupported or non-bitmap format for a single
+/// glyph shouldn't abort listing or exporting the rest of the strike.
+fn decode_glyph_image(
+    index_subtable: &IndexSubTable,
+    image: &[u8],
+) -> Result<Option<BitmapData>, Error> {
+    match index_subtable.image_format() {
+        // PNG-wrapped color glyphs (CBDT format 17-19): the stored bytes
+        // already are a complete PNG we can pass straight through.
+        17 | 18 | 19 => Ok(Some(BitmapData::Png(image.to_vec()))),
+        // Byte-aligned monochrome: each row is padded out to a byte
+        // boundary on disk.
+        1 | 6 => Ok(Some(BitmapData::reconstruct(index_subtable, image, false)?)),
+        // Bit-aligned monochrome: rows are packed back-to-back with no
+        // padding.
+        2 | 5 | 7 => Ok(Some(BitmapData::reconstruct(index_subtable, image, true)?)),
+        // Formats 8 and 9 aren't bitmaps at all - they're lists of glyph
+        // component references (like a composite `glyf` glyph), which this
+        // tool doesn't resolve.
+        8 | 9 => {
+            eprintln!(
+                "EBDT/CBDT format {} glyphs are composite references, not bitmaps; skipping glyph",
+                index_subtable.image_format()
+            );
+            Ok(None)
+        }
+        other => {
+            eprintln!(
+                "unsupported EBDT/CBDT image format {}, skipping glyph",
+                other
+            );
+            Ok(None)
+        }
+    }
+}
+
+fn read_sbix_strikes(scope: ReadScope, ttf: &OffsetTable) -> Result<Vec<Strike>, Error> {
+    let sbix_data = match ttf.read_table(scope, tag::SBIX)? {
+        Some(data) => data,
+        None => return Ok(Vec::new()),
+    };
+    let num_glyphs = ttf
+        .read_table(scope, tag::MAXP)?
+        .ok_or(Error::Message("no maxp table"))?
+        .read::<MaxpTable>()?
+        .num_glyphs;
+
+    let sbix = sbix_data.read_dep::<SbixTable>(usize::from(num_glyphs))?;
+
+    let mut strikes = Vec::with_capacity(sbix.strikes.len());
+    for strike in &sbix.strikes {
+        strikes.push(sbix_to_strike(strike));
+    }
+    Ok(strikes)
+}
+
+fn sbix_to_strike(strike: &SbixStrike) -> Strike {
+    let glyphs = strike
+        .glyphs
+        .iter()
+        .filter_map(|glyph| {
+            glyph
+                .data
+                .as_ref()
+                .map(|data| (glyph.glyph_id, BitmapData::Png(data.clone())))
+        })
+        .collect();
+    Strike {
+        source: "sbix",
+        ppem_x: strike.ppem,
+        ppem_y: strike.ppem,
+        bit_depth: 32,
+        glyphs,
+    }
+}
+
+fn list_strikes(strikes: &[Strike]) {
+    for strike in strikes {
+        println!(
+            "{} strike: {}x{} ppem, {}-bit, {} glyphs",
+            strike.source,
+            strike.ppem_x,
+            strike.ppem_y,
+            strike.bit_depth,
+            strike.glyphs.len()
+        );
+        let mut glyph_ids: Vec<u16> = strike
+            .glyphs
+            .iter()
+            .map(|(glyph_id, _)| *glyph_id)
+            .collect();
+        glyph_ids.sort_unstable();
+        if let (Some(&first), Some(&last)) = (glyph_ids.first(), glyph_ids.last()) {
+            println!(" - glyph range: {}..={}", first, last);
+        }
+        for (glyph_id, data) in &strike.glyphs {
+            println!(" - glyph {}: {}", glyph_id, data.format_name());
+        }
+        println!();
+    }
+}
+
+fn export_strikes(dir: &str, strikes: &[Strike]) -> Result<(), Error> {
+    fs::create_dir_all(dir)?;
+    for strike in strikes {
+        for (glyph_id, data) in &strike.glyphs {
+            let filename = format!(
+                "{}/{}-{}x{}-glyph{}.png",
+                dir, strike.source, strike.ppem_x, strike.ppem_y, glyph_id
+            );
+            let png_bytes = data.to_png()?;
+            let mut out = File::create(&filename)?;
+            out.write_all(&png_bytes)?;
+        }
+    }
+    println!("exported bitmaps to {}", dir);
+    Ok(())
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
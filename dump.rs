@@ -2,9 +2,11 @@ use atty::Stream;
 use encoding_rs::{Encoding, MACINTOSH, UTF_16BE};
 use getopts::Options;
 
+use fontcode::cmap::{Cmap, CmapSubtable, EncodingRecord};
 use fontcode::error::ParseError;
 use fontcode::font_tables;
 use fontcode::fontfile::FontFile;
+use fontcode::glyph_index::read_cmap_subtable;
 use fontcode::read::ReadScope;
 use fontcode::tables::loca::LocaTable;
 use fontcode::tables::{HeadTable, MaxpTable, NameTable, OffsetTable, OpenTypeFont, TTCHeader};
@@ -35,6 +37,11 @@ fn main() -> Result<(), Error> {
     opts.optopt("t", "table", "dump the content of this table", "TABLE");
     opts.optopt("i", "index", "index of the font to dump (for TTC)", "INDEX");
     opts.optflag("l", "loca", "print the loca table");
+    opts.optflag(
+        "c",
+        "cmap",
+        "print a semantic dump of the cmap table as coverage ranges",
+    );
     opts.optflag("h", "help", "print this help menu");
 
     let matches = match opts.parse(&args[1..]) {
@@ -73,6 +80,8 @@ fn main() -> Result<(), Error> {
 
     if matches.opt_present("l") {
         dump_loca_table(&buffer, index)?;
+    } else if matches.opt_present("c") {
+        dump_cmap_info(&buffer)?;
     } else {
         match ReadScope::new(&buffer).read::<FontFile>()? {
             FontFile::OpenType(font_file) => match font_file.font {
@@ -316,6 +325,90 @@ fn dump_loca_table(buffer: &[u8], index: usize) -> Result<(), ParseError> {
     Ok(())
 }
 
+/// Parse the `cmap` table, list every `EncodingRecord` with its
+/// platform/encoding ids and subtable format, then print the codepoint to
+/// glyph mapping of the best Unicode subtable collapsed into contiguous
+/// coverage ranges (start_codepoint, end_codepoint, start_glyph). Flags
+/// gaps and overlaps between ranges so the dump doubles as a coverage
+/// audit.
+fn dump_cmap_info(buffer: &[u8]) -> Result<(), Error> {
+    let font = font_tables::FontImpl::new(buffer, 0).unwrap();
+    let provider = font_tables::FontTablesImpl::FontImpl(font);
+
+    let table = provider.get_table(tag::CMAP).expect("no cmap table");
+    let scope = ReadScope::new(table.borrow());
+    let cmap = scope.read::<Cmap>()?;
+
+    println!("cmap:");
+    println!(" - num_encoding_records: {}", cmap.encoding_records.len());
+    for record in &cmap.encoding_records {
+        println!(
+            " - platform: {}, encoding: {}, format: {}",
+            record.platform_id,
+            record.encoding_id,
+            cmap_subtable_format(&cmap, &record)?
+        );
+    }
+    println!();
+
+    let cmap_subtable = match read_cmap_subtable(&cmap)? {
+        Some(cmap_subtable) => cmap_subtable,
+        None => {
+            println!("no suitable Unicode subtable to dump coverage for");
+            return Ok(());
+        }
+    };
+
+    let ranges = coverage_ranges(&cmap_subtable)?;
+    println!("coverage ranges ({}):", ranges.len());
+    let mut prev_end: Option<u32> = None;
+    for &(start, end, start_glyph) in &ranges {
+        let flag = match prev_end {
+            Some(prev_end) if start == prev_end + 1 => "",
+            Some(prev_end) if start <= prev_end => " (overlap)",
+            Some(_) => " (gap)",
+            None => "",
+        };
+        println!(
+            "U+{:04X}..U+{:04X} -> glyph {}{}",
+            start, end, start_glyph, flag
+        );
+        prev_end = Some(end);
+    }
+
+    Ok(())
+}
+
+fn cmap_subtable_format(cmap: &Cmap, record: &EncodingRecord) -> Result<u16, ParseError> {
+    let subtable_scope = cmap.scope.offset(record.offset as usize);
+    let format = subtable_scope
+        .data()
+        .get(0..2)
+        .ok_or(ParseError::BadOffset)?;
+    Ok(u16::from_be_bytes([format[0], format[1]]))
+}
+
+/// Walk every mapped codepoint in ascending order, merging adjacent
+/// (codepoint, glyph) pairs into a single range whenever the glyph id
+/// also increases by one. Supports format 0, 4, 6, and 12 subtables via
+/// `CmapSubtable`'s common mapping API.
+fn coverage_ranges(cmap_subtable: &CmapSubtable) -> Result<Vec<(u32, u32, u16)>, ParseError> {
+    let mut ranges: Vec<(u32, u32, u16)> = Vec::new();
+    for ch in 0..=0x10_FFFFu32 {
+        if let Some(glyph_id) = cmap_subtable.map_glyph(ch)? {
+            match ranges.last_mut() {
+                Some((start, end, start_glyph))
+                    if ch == *end + 1 && glyph_id == *start_glyph + (*end - *start) as u16 + 1 =>
+                {
+                    *end = ch;
+                }
+                _ => ranges.push((ch, ch, glyph_id)),
+            }
+        }
+    }
+    Ok(ranges)
+}
+
 fn dump_raw_table(scope: Option<ReadScope>) -> Result<(), Error> {
     if let Some(scope) = scope {
         io::stdout().write_all(scope.data()).map_err(Error::from)
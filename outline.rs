@@ -0,0 +1,379 @@
+use getopts::Options;
+
+use fontcode::cmap::Cmap;
+use fontcode::error::ParseError;
+use fontcode::glyph_index::read_cmap_subtable;
+use fontcode::read::ReadScope;
+use fontcode::tables::glyf::{
+    CompositeGlyphComponent, GlyfRecord, GlyfTable, Glyph, GlyphData, Point,
+};
+use fontcode::tables::loca::LocaTable;
+use fontcode::tables::{HeadTable, MaxpTable, OffsetTable, OpenTypeFile, OpenTypeFont};
+use fontcode::tag;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    Message(&'static str),
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optopt(
+        "g",
+        "glyphs",
+        "comma separated glyph ids to extract outlines for",
+        "IDS",
+    );
+    opts.optopt(
+        "c",
+        "chars",
+        "characters to extract outlines for, resolved via cmap",
+        "TEXT",
+    );
+    opts.optflag("h", "help", "print this help menu");
+
+    let matches = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => panic!(f.to_string()),
+    };
+
+    if matches.opt_present("h") || matches.free.is_empty() {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+
+    let filename = &matches.free[0];
+    let buffer = read_file(filename)?;
+    let fontfile = ReadScope::new(&buffer).read::<OpenTypeFile>()?;
+    let ttf = match fontfile.font {
+        OpenTypeFont::Single(ttf) => ttf,
+        OpenTypeFont::Collection(_) => return Err(Error::Message("TTC not supported")),
+    };
+
+    let glyph_ids = resolve_glyph_ids(fontfile.scope, &ttf, &matches)?;
+    print_outlines(fontfile.scope, &ttf, &glyph_ids)
+}
+
+fn print_usage(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options] FONTFILE ", program);
+    eprint!("{}", opts.usage(&brief));
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, io::Error> {
+    let mut file = File::open(path)?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn resolve_glyph_ids(
+    scope: ReadScope,
+    ttf: &OffsetTable,
+    matches: &getopts::Matches,
+) -> Result<Vec<u16>, Error> {
+    if let Some(ids) = matches.opt_str("g") {
+        return ids
+            .split(',')
+            .map(|id| {
+                id.trim()
+                    .parse::<u16>()
+                    .map_err(|_| Error::Message("bad glyph id"))
+            })
+            .collect();
+    }
+
+    let chars = matches.opt_str("c").unwrap_or_default();
+    let cmap = ttf
+        .read_table(scope, tag::CMAP)?
+        .ok_or(Error::Message("no cmap table"))?
+        .read::<Cmap>()?;
+    let cmap_subtable =
+        read_cmap_subtable(&cmap)?.ok_or(Error::Message("no suitable cmap subtable"))?;
+
+    chars
+        .chars()
+        .map(|ch| {
+            cmap_subtable
+                .map_glyph(ch as u32)?
+                .ok_or(Error::Message("character not in cmap"))
+        })
+        .collect()
+}
+
+/// Print one SVG `<path>` per requested glyph id, resolving composite
+/// glyphs recursively and concatenating their transformed components.
+fn print_outlines(scope: ReadScope, ttf: &OffsetTable, glyph_ids: &[u16]) -> Result<(), Error> {
+    let head = ttf
+        .read_table(scope, tag::HEAD)?
+        .ok_or(Error::Message("no head table"))?
+        .read::<HeadTable>()?;
+    let maxp = ttf
+        .read_table(scope, tag::MAXP)?
+        .ok_or(Error::Message("no maxp table"))?
+        .read::<MaxpTable>()?;
+    let loca = ttf
+        .read_table(scope, tag::LOCA)?
+        .ok_or(Error::Message("no loca table"))?
+        .read_dep::<LocaTable>((usize::from(maxp.num_glyphs), head.index_to_loc_format))?;
+    let glyf = ttf
+        .read_table(scope, tag::GLYF)?
+        .ok_or(Error::Message("no glyf table"))?
+        .read_dep::<GlyfTable>(&loca)?;
+
+    let units_per_em = f64::from(head.units_per_em);
+
+    for &glyph_id in glyph_ids {
+        let contours = resolve_outline(&glyf, glyph_id, &Transform::identity(), 0)?;
+        // TrueType's y-axis points up; SVG's points down. Flip here, before
+        // building the path and the viewBox, so both agree on the same
+        // (now SVG-native) coordinate space.
+        let flipped: Vec<Contour> = contours
+            .iter()
+            .map(|contour| {
+                contour
+                    .iter()
+                    .map(|&(x, y, on_curve)| (x, -y, on_curve))
+                    .collect()
+            })
+            .collect();
+        let path = contours_to_path(&flipped);
+        let (min_x, min_y, max_x, max_y) = bounding_box(&flipped, units_per_em);
+        println!(
+            "glyph {}: <path d=\"{}\" /> (viewBox=\"{} {} {} {}\")",
+            glyph_id,
+            path,
+            min_x,
+            min_y,
+            max_x - min_x,
+            max_y - min_y
+        );
+    }
+
+    Ok(())
+}
+
+/// The bounding box of every point across `contours`, already in the
+/// (flipped) SVG coordinate space. Glyphs commonly have a negative LSB or
+/// descenders, and can exceed `units_per_em` in y for tall ascenders, so
+/// a fixed `0 0 upm upm` viewBox clips real glyphs; falls back to a
+/// `units_per_em`-square box for glyphs with no contours (e.g. space).
+fn bounding_box(contours: &[Contour], units_per_em: f64) -> (f64, f64, f64, f64) {
+    let mut min_x = f64::INFINITY;
+    let mut min_y = f64::INFINITY;
+    let mut max_x = f64::NEG_INFINITY;
+    let mut max_y = f64::NEG_INFINITY;
+    for contour in contours {
+        for &(x, y, _) in contour {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+    if min_x.is_finite() {
+        (min_x, min_y, max_x, max_y)
+    } else {
+        (0.0, -units_per_em, units_per_em, 0.0)
+    }
+}
+
+/// A glyph outline resolved to absolute font units: one contour per
+/// closed loop, one point per (x, y, on_curve) vertex.
+type Contour = Vec<(f64, f64, bool)>;
+
+#[derive(Clone, Copy)]
+struct Transform {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    dx: f64,
+    dy: f64,
+}
+
+impl Transform {
+    fn identity() -> Self {
+        Transform {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            dx: 0.0,
+            dy: 0.0,
+        }
+    }
+
+    fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.dx,
+            self.b * x + self.d * y + self.dy,
+        )
+    }
+
+    fn then(&self, component: &CompositeGlyphComponent) -> Transform {
+        let (m00, m01, m10, m11) = component.transform_matrix();
+        let (dx, dy) = component.offset();
+        Transform {
+            a: self.a * m00 + self.c * m01,
+            b: self.b * m00 + self.d * m01,
+            c: self.a * m10 + self.c * m11,
+            d: self.b * m10 + self.d * m11,
+            dx: self.a * dx + self.c * dy + self.dx,
+            dy: self.b * dx + self.d * dy + self.dy,
+        }
+    }
+}
+
+// Composite glyphs are recursed into by glyph id with no cycle detection
+// in the font itself, so a self-referencing or mutually-recursive
+// component (A -> A, or A -> B -> A) would otherwise recurse forever;
+// bail out past any plausible real nesting depth, same guard subset.rs
+// uses for composite depth.
+const MAX_COMPOSITE_DEPTH: u16 = 16;
+
+fn resolve_outline(
+    glyf: &GlyfTable,
+    glyph_id: u16,
+    transform: &Transform,
+    depth: u16,
+) -> Result<Vec<Contour>, Error> {
+    if depth > MAX_COMPOSITE_DEPTH {
+        return Ok(Vec::new());
+    }
+    match glyf.records.get(usize::from(glyph_id)) {
+        Some(GlyfRecord::Present(glyph)) => outline_for_glyph(glyf, glyph, transform, depth),
+        _ => Ok(Vec::new()),
+    }
+}
+
+fn outline_for_glyph(
+    glyf: &GlyfTable,
+    glyph: &Glyph,
+    transform: &Transform,
+    depth: u16,
+) -> Result<Vec<Contour>, Error> {
+    match &glyph.data {
+        GlyphData::Simple(simple) => Ok(simple
+            .contours
+            .iter()
+            .map(|points| {
+                points
+                    .iter()
+                    .map(|point: &Point| {
+                        let (x, y) = transform.apply(f64::from(point.x), f64::from(point.y));
+                        (x, y, point.on_curve)
+                    })
+                    .collect()
+            })
+            .collect()),
+        GlyphData::Composite { glyphs, .. } => {
+            let mut contours = Vec::new();
+            for component in glyphs {
+                let component_transform = transform.then(component);
+                contours.extend(resolve_outline(
+                    glyf,
+                    component.glyph_index,
+                    &component_transform,
+                    depth + 1,
+                )?);
+            }
+            Ok(contours)
+        }
+    }
+}
+
+/// Convert resolved contours to SVG path data: `M` to start, `Q` for
+/// quadratic curve segments (TrueType has a single off-curve control
+/// point), `L` for straight segments, `Z` to close. Consecutive off-curve
+/// points imply a synthesized on-curve midpoint between them. Each path
+/// starts at the contour's first on-curve point; a contour with no
+/// on-curve point at all begins at the midpoint of its last and first
+/// points.
+fn contours_to_path(contours: &[Contour]) -> String {
+    let mut out = String::new();
+    for contour in contours {
+        if contour.is_empty() {
+            continue;
+        }
+
+        let start_index = contour.iter().position(|&(_, _, on_curve)| on_curve);
+        let (start, points): (_, Vec<_>) = match start_index {
+            Some(index) => (
+                contour[index],
+                contour[index + 1..]
+                    .iter()
+                    .chain(contour[..=index].iter())
+                    .copied()
+                    .collect(),
+            ),
+            None => {
+                // No on-curve point at all: synthesize one from the
+                // midpoint of the last and first (both off-curve) points.
+                let (lx, ly, _) = *contour.last().unwrap();
+                let (fx, fy, _) = contour[0];
+                let synthesized = ((lx + fx) / 2.0, (ly + fy) / 2.0, true);
+                (synthesized, contour.to_vec())
+            }
+        };
+
+        out.push_str(&format!("M{:.2},{:.2} ", start.0, start.1));
+
+        let mut pending_off_curve: Option<(f64, f64)> = None;
+        let mut cursor = start;
+        for &(x, y, on_curve) in &points {
+            if on_curve {
+                match pending_off_curve.take() {
+                    Some((cx, cy)) => {
+                        out.push_str(&format!("Q{:.2},{:.2} {:.2},{:.2} ", cx, cy, x, y));
+                    }
+                    None => {
+                        out.push_str(&format!("L{:.2},{:.2} ", x, y));
+                    }
+                }
+                cursor = (x, y, true);
+            } else if let Some((cx, cy)) = pending_off_curve {
+                // Two consecutive off-curve points imply an on-curve
+                // midpoint between them.
+                let mx = (cx + x) / 2.0;
+                let my = (cy + y) / 2.0;
+                out.push_str(&format!("Q{:.2},{:.2} {:.2},{:.2} ", cx, cy, mx, my));
+                pending_off_curve = Some((x, y));
+                cursor = (mx, my, true);
+            } else {
+                pending_off_curve = Some((x, y));
+            }
+        }
+
+        if let Some((cx, cy)) = pending_off_curve {
+            out.push_str(&format!(
+                "Q{:.2},{:.2} {:.2},{:.2} ",
+                cx, cy, start.0, start.1
+            ));
+        }
+        let _ = cursor;
+        out.push('Z');
+        out.push(' ');
+    }
+    out.trim_end().to_string()
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -1,10 +1,13 @@
 use fontcode::cmap::{Cmap, CmapSubtable};
 use fontcode::error::{ParseError, ShapingError};
 use fontcode::glyph_index::read_cmap_subtable;
+use fontcode::gpos::{gpos_apply, GposGlyphInfo};
 use fontcode::gsub::{gsub_apply_default, GlyphOrigin, RawGlyph};
 use fontcode::layout::{GDEFTable, LayoutTable, LayoutTableType};
 use fontcode::read::ReadScope;
-use fontcode::tables::{OffsetTable, OpenTypeFile, OpenTypeFont, TTCHeader};
+use fontcode::tables::{
+    HheaTable, HmtxTable, MaxpTable, OffsetTable, OpenTypeFile, OpenTypeFont, TTCHeader,
+};
 use fontcode::tag;
 use std::env;
 use std::fs::File;
@@ -111,9 +114,95 @@ fn shape_ttf<'a>(
     } else {
         println!("no GSUB table");
     }
+
+    let hmtx = read_hmtx(scope, &ttf)?;
+    let positions = position_glyphs(scope, &ttf, script, lang, &glyphs)?;
+    print_positions(&glyphs, &positions, hmtx.as_ref());
+
     Ok(())
 }
 
+/// Apply GPOS (single, pair/class-based kerning, and mark attachment)
+/// to `glyphs`, returning one placement per glyph. Returns `None` when
+/// the font has no GPOS table, so the caller can fall back to `hmtx`.
+fn position_glyphs<'a>(
+    scope: ReadScope<'a>,
+    ttf: &OffsetTable<'a>,
+    script: u32,
+    lang: u32,
+    glyphs: &[RawGlyph<()>],
+) -> Result<Option<Vec<GposGlyphInfo>>, ShapingError> {
+    let gpos_record = match ttf.find_table_record(tag::GPOS) {
+        Some(record) => record,
+        None => {
+            println!("no GPOS table");
+            return Ok(None);
+        }
+    };
+    let gpos_table_data = gpos_record.read_table(scope)?.data();
+    let opt_gdef_table_data = match ttf.find_table_record(tag::GDEF) {
+        Some(gdef_record) => Some(gdef_record.read_table(scope)?.data()),
+        None => None,
+    };
+    let vertical = false;
+    let positions = with_tables(
+        gpos_table_data,
+        opt_gdef_table_data,
+        |gpos_table, opt_gdef_table| {
+            gpos_apply(&gpos_table, opt_gdef_table, vertical, script, lang, glyphs)
+        },
+    )?;
+    Ok(Some(positions))
+}
+
+fn read_hmtx<'a>(
+    scope: ReadScope<'a>,
+    ttf: &OffsetTable<'a>,
+) -> Result<Option<HmtxTable<'a>>, ShapingError> {
+    let maxp = match ttf.read_table(scope, tag::MAXP)? {
+        Some(data) => data.read::<MaxpTable>()?,
+        None => return Ok(None),
+    };
+    let hhea = match ttf.read_table(scope, tag::HHEA)? {
+        Some(data) => data.read::<HheaTable>()?,
+        None => return Ok(None),
+    };
+    let hmtx = match ttf.read_table(scope, tag::HMTX)? {
+        Some(data) => data.read_dep::<HmtxTable>((
+            usize::from(maxp.num_glyphs),
+            usize::from(hhea.num_h_metrics),
+        ))?,
+        None => return Ok(None),
+    };
+    Ok(Some(hmtx))
+}
+
+/// Print a `glyph_id, x_offset, y_offset, x_advance` row per glyph. Per
+/// the GPOS spec, a ValueRecord's `XAdvance` is an adjustment *added to*
+/// the glyph's default advance, not a replacement for it, so `x_advance`
+/// is always `hmtx`'s advance width plus whatever delta GPOS reported
+/// (zero for glyphs no lookup touched).
+fn print_positions(
+    glyphs: &[RawGlyph<()>],
+    positions: &Option<Vec<GposGlyphInfo>>,
+    hmtx: Option<&HmtxTable>,
+) {
+    println!("glyph_id, x_offset, y_offset, x_advance");
+    for (i, glyph) in glyphs.iter().enumerate() {
+        let glyph_id = glyph.glyph_index.unwrap_or(0);
+        let gpos_info = positions.as_ref().and_then(|positions| positions.get(i));
+        let hmtx_advance = hmtx
+            .and_then(|hmtx| hmtx.h_metric(glyph_id))
+            .map(|(advance_width, _)| i32::from(advance_width))
+            .unwrap_or(0);
+        let (x_offset, y_offset, x_advance) = match gpos_info {
+            Some(info) => (info.x_offset, info.y_offset, hmtx_advance + info.x_advance),
+            None => (0, 0, hmtx_advance),
+        };
+        println!("{}, {}, {}, {}", glyph_id, x_offset, y_offset, x_advance);
+    }
+}
+
 fn with_tables<T: LayoutTableType, Ret>(
     layout_table_data: &[u8],
     opt_gdef_table_data: Option<&[u8]>,
@@ -0,0 +1,785 @@
+use getopts::Options;
+
+use fontcode::cmap::Cmap;
+use fontcode::error::ParseError;
+use fontcode::glyph_index::read_cmap_subtable;
+use fontcode::read::ReadScope;
+use fontcode::tables::glyf::{GlyfRecord, GlyfTable, Glyph, GlyphData};
+use fontcode::tables::loca::LocaTable;
+use fontcode::tables::{
+    HeadTable, HheaTable, HmtxTable, MaxpTable, OffsetTable, OpenTypeFile, OpenTypeFont,
+};
+use fontcode::tag;
+
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    Message(&'static str),
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optopt(
+        "t",
+        "text",
+        "subset to the glyphs needed to render TEXT",
+        "TEXT",
+    );
+    opts.optopt(
+        "o",
+        "output",
+        "write the subsetted font to FILE (default subsetted.ttf)",
+        "FILE",
+    );
+    opts.optflag("h", "help", "print this help menu");
+
+    let matches = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => panic!(f.to_string()),
+    };
+
+    if matches.opt_present("h") || matches.free.is_empty() {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+
+    let filename = &matches.free[0];
+    let text = matches.opt_str("t").unwrap_or_default();
+    let output = matches
+        .opt_str("o")
+        .unwrap_or_else(|| "subsetted.ttf".to_string());
+
+    let buffer = read_file(filename)?;
+    let fontfile = ReadScope::new(&buffer).read::<OpenTypeFile>()?;
+    let ttf = match fontfile.font {
+        OpenTypeFont::Single(ttf) => ttf,
+        OpenTypeFont::Collection(_) => return Err(Error::Message("TTC not supported")),
+    };
+
+    let chars: BTreeSet<char> = text.chars().collect();
+    let subset_data = subset(fontfile.scope, &ttf, &chars)?;
+
+    let mut out = File::create(&output)?;
+    out.write_all(&subset_data)?;
+    println!("wrote {} ({} bytes)", output, subset_data.len());
+
+    Ok(())
+}
+
+fn print_usage(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options] FONTFILE ", program);
+    eprint!("{}", opts.usage(&brief));
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, io::Error> {
+    let mut file = File::open(path)?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Build a new sfnt containing only the glyphs needed to render `chars`,
+/// plus any glyphs pulled in transitively via composite glyph references.
+fn subset<'a>(
+    scope: ReadScope<'a>,
+    ttf: &OffsetTable<'a>,
+    chars: &BTreeSet<char>,
+) -> Result<Vec<u8>, Error> {
+    let head = ttf
+        .read_table(scope, tag::HEAD)?
+        .ok_or(Error::Message("no head table"))?
+        .read::<HeadTable>()?;
+    let maxp = ttf
+        .read_table(scope, tag::MAXP)?
+        .ok_or(Error::Message("no maxp table"))?
+        .read::<MaxpTable>()?;
+    let hhea = ttf
+        .read_table(scope, tag::HHEA)?
+        .ok_or(Error::Message("no hhea table"))?
+        .read::<HheaTable>()?;
+    let hmtx = ttf
+        .read_table(scope, tag::HMTX)?
+        .ok_or(Error::Message("no hmtx table"))?
+        .read_dep::<HmtxTable>((
+            usize::from(maxp.num_glyphs),
+            usize::from(hhea.num_h_metrics),
+        ))?;
+    let loca = ttf
+        .read_table(scope, tag::LOCA)?
+        .ok_or(Error::Message("no loca table"))?
+        .read_dep::<LocaTable>((usize::from(maxp.num_glyphs), head.index_to_loc_format))?;
+    let glyf = ttf
+        .read_table(scope, tag::GLYF)?
+        .ok_or(Error::Message("no glyf table"))?
+        .read_dep::<GlyfTable>(&loca)?;
+    let cmap = ttf
+        .read_table(scope, tag::CMAP)?
+        .ok_or(Error::Message("no cmap table"))?
+        .read::<Cmap>()?;
+    let cmap_subtable =
+        read_cmap_subtable(&cmap)?.ok_or(Error::Message("no suitable cmap subtable"))?;
+
+    // Map requested characters to glyph ids.
+    let mut char_to_old_glyph: Vec<(char, u16)> = Vec::new();
+    for &ch in chars {
+        if let Some(glyph_index) = cmap_subtable.map_glyph(ch as u32)? {
+            char_to_old_glyph.push((ch, glyph_index));
+        }
+    }
+
+    // Compute the glyph closure: requested glyphs plus every component
+    // referenced (directly or transitively) by a composite glyph.
+    let mut wanted: BTreeSet<u16> = BTreeSet::new();
+    let mut frontier: Vec<u16> = char_to_old_glyph.iter().map(|&(_, id)| id).collect();
+    frontier.push(0); // .notdef always survives, and is always glyph 0
+    while let Some(glyph_id) = frontier.pop() {
+        if !wanted.insert(glyph_id) {
+            continue;
+        }
+        for component_id in composite_component_ids(&glyf, glyph_id) {
+            if !wanted.contains(&component_id) {
+                frontier.push(component_id);
+            }
+        }
+    }
+
+    // Old -> new glyph id remap. BTreeSet iterates in ascending order, and
+    // glyph 0 is always the smallest id, so it lands at new id 0.
+    let old_to_new: HashMap<u16, u16> = wanted
+        .iter()
+        .enumerate()
+        .map(|(new_id, &old_id)| (old_id, new_id as u16))
+        .collect();
+
+    let new_records: Vec<GlyfRecord> = wanted
+        .iter()
+        .map(|&old_id| remap_glyph(&glyf, old_id, &old_to_new))
+        .collect::<Result<_, _>>()?;
+
+    let fallback_metric = hmtx
+        .h_metric(maxp.num_glyphs - 1)
+        .ok_or(Error::Message("hmtx missing final metric"))?;
+    let new_hmtx: Vec<(u16, i16)> = wanted
+        .iter()
+        .map(|&old_id| hmtx.h_metric(old_id).unwrap_or(fallback_metric))
+        .collect();
+
+    let new_cmap_entries: Vec<(u32, u16)> = char_to_old_glyph
+        .into_iter()
+        .filter_map(|(ch, old_id)| old_to_new.get(&old_id).map(|&new_id| (ch as u32, new_id)))
+        .collect();
+
+    write_font(
+        scope,
+        ttf,
+        &head,
+        &hhea,
+        &maxp,
+        &new_records,
+        &new_hmtx,
+        &new_cmap_entries,
+    )
+}
+
+fn composite_component_ids(glyf: &GlyfTable, glyph_id: u16) -> Vec<u16> {
+    match glyf.records.get(usize::from(glyph_id)) {
+        Some(GlyfRecord::Present(Glyph {
+            data: GlyphData::Composite { glyphs, .. },
+            ..
+        })) => glyphs
+            .iter()
+            .map(|component| component.glyph_index)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Clone a glyph record, rewriting any composite component glyph indices
+/// through the old->new remap table.
+fn remap_glyph(
+    glyf: &GlyfTable,
+    old_id: u16,
+    old_to_new: &HashMap<u16, u16>,
+) -> Result<GlyfRecord, Error> {
+    match glyf.records.get(usize::from(old_id)) {
+        Some(GlyfRecord::Present(glyph)) => {
+            let mut glyph = glyph.clone();
+            if let GlyphData::Composite { glyphs, .. } = &mut glyph.data {
+                for component in glyphs.iter_mut() {
+                    component.glyph_index = *old_to_new
+                        .get(&component.glyph_index)
+                        .ok_or(Error::Message("component glyph missing from subset"))?;
+                }
+            }
+            Ok(GlyfRecord::Present(glyph))
+        }
+        _ => Ok(GlyfRecord::Empty),
+    }
+}
+
+/// Assemble the final sfnt: copy `name` and `OS/2` through verbatim,
+/// rebuild head/hhea/loca/glyf/hmtx/maxp/cmap for the subset, and
+/// recompute every table checksum plus the head checkAdjustment. No other
+/// table of the source font is carried over.
+fn write_font(
+    scope: ReadScope,
+    ttf: &OffsetTable,
+    head: &HeadTable,
+    hhea: &HheaTable,
+    maxp: &MaxpTable,
+    records: &[GlyfRecord],
+    hmtx: &[(u16, i16)],
+    cmap_entries: &[(u32, u16)],
+) -> Result<Vec<u8>, Error> {
+    let (loca_data, glyf_data, index_to_loc_format) = write_loca_and_glyf(records)?;
+
+    let mut new_head = head.clone();
+    new_head.index_to_loc_format = index_to_loc_format;
+    new_head.check_sum_adjustment = 0;
+
+    let mut tables: Vec<(u32, Vec<u8>)> = vec![
+        (tag::HEAD, write_head(&new_head)),
+        (tag::HHEA, write_hhea(hhea, records.len() as u16)),
+        (tag::MAXP, write_maxp(maxp, records)),
+        (tag::HMTX, write_hmtx(hmtx)),
+        (tag::CMAP, write_cmap(cmap_entries)),
+        (tag::LOCA, loca_data),
+        (tag::GLYF, glyf_data),
+    ];
+    // `post` format 2.0 carries a numberOfGlyphs-sized glyph name index
+    // keyed to the original glyph order, which the remap above would
+    // leave stale; drop it rather than copy something inconsistent with
+    // the new `maxp.numGlyphs`.
+    for &pass_tag in &[tag::NAME, tag::OS_2] {
+        if let Some(data) = pass_through(scope, ttf, pass_tag).ok() {
+            tables.push((pass_tag, data));
+        }
+    }
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    Ok(assemble_sfnt(ttf.sfnt_version, tables))
+}
+
+fn pass_through(scope: ReadScope, ttf: &OffsetTable, table_tag: u32) -> Result<Vec<u8>, Error> {
+    ttf.read_table(scope, table_tag)?
+        .map(|table| table.data().to_vec())
+        .ok_or(Error::Message("pass-through table missing"))
+}
+
+fn write_head(head: &HeadTable) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(54);
+    buf.extend_from_slice(&((head.version) as u32).to_be_bytes());
+    buf.extend_from_slice(&(head.font_revision as u32).to_be_bytes());
+    buf.extend_from_slice(&0u32.to_be_bytes()); // checkSumAdjustment, filled in later
+    buf.extend_from_slice(&head.magic_number.to_be_bytes());
+    buf.extend_from_slice(&head.flags.to_be_bytes());
+    buf.extend_from_slice(&head.units_per_em.to_be_bytes());
+    buf.extend_from_slice(&head.created.to_be_bytes());
+    buf.extend_from_slice(&head.modified.to_be_bytes());
+    buf.extend_from_slice(&head.x_min.to_be_bytes());
+    buf.extend_from_slice(&head.y_min.to_be_bytes());
+    buf.extend_from_slice(&head.x_max.to_be_bytes());
+    buf.extend_from_slice(&head.y_max.to_be_bytes());
+    buf.extend_from_slice(&head.mac_style.to_be_bytes());
+    buf.extend_from_slice(&head.lowest_rec_ppem.to_be_bytes());
+    buf.extend_from_slice(&head.font_direction_hint.to_be_bytes());
+    buf.extend_from_slice(&head.index_to_loc_format.to_be_bytes());
+    buf.extend_from_slice(&head.glyph_data_format.to_be_bytes());
+    buf
+}
+
+/// Rebuild `hhea` for the subset, keeping every field verbatim except
+/// `numberOfHMetrics`, which must not exceed the new `numGlyphs`.
+fn write_hhea(hhea: &HheaTable, num_h_metrics: u16) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(36);
+    buf.extend_from_slice(&(hhea.version as u32).to_be_bytes());
+    buf.extend_from_slice(&hhea.ascender.to_be_bytes());
+    buf.extend_from_slice(&hhea.descender.to_be_bytes());
+    buf.extend_from_slice(&hhea.line_gap.to_be_bytes());
+    buf.extend_from_slice(&hhea.advance_width_max.to_be_bytes());
+    buf.extend_from_slice(&hhea.min_left_side_bearing.to_be_bytes());
+    buf.extend_from_slice(&hhea.min_right_side_bearing.to_be_bytes());
+    buf.extend_from_slice(&hhea.x_max_extent.to_be_bytes());
+    buf.extend_from_slice(&hhea.caret_slope_rise.to_be_bytes());
+    buf.extend_from_slice(&hhea.caret_slope_run.to_be_bytes());
+    buf.extend_from_slice(&hhea.caret_offset.to_be_bytes());
+    buf.extend_from_slice(&0i16.to_be_bytes()); // reserved
+    buf.extend_from_slice(&0i16.to_be_bytes()); // reserved
+    buf.extend_from_slice(&0i16.to_be_bytes()); // reserved
+    buf.extend_from_slice(&0i16.to_be_bytes()); // reserved
+    buf.extend_from_slice(&hhea.metric_data_format.to_be_bytes());
+    buf.extend_from_slice(&num_h_metrics.to_be_bytes());
+    buf
+}
+
+/// Rebuild `maxp` as version 1.0 (required for a `glyf`/`loca` outline
+/// font; version 0.5 is CFF-only), recomputing the glyph-count-dependent
+/// maxima from the subsetted glyph set and carrying the hinting-program
+/// maxima (storage, zones, stack, function/instruction defs) over from
+/// the source font unchanged. The `fpgm`/`prep` programs themselves are
+/// not copied into the subset.
+fn write_maxp(maxp: &MaxpTable, records: &[GlyfRecord]) -> Vec<u8> {
+    let maxima = compute_glyph_maxima(records);
+
+    let mut buf = Vec::with_capacity(32);
+    buf.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    buf.extend_from_slice(&(records.len() as u16).to_be_bytes());
+    buf.extend_from_slice(&maxima.max_points.to_be_bytes());
+    buf.extend_from_slice(&maxima.max_contours.to_be_bytes());
+    buf.extend_from_slice(&maxima.max_composite_points.to_be_bytes());
+    buf.extend_from_slice(&maxima.max_composite_contours.to_be_bytes());
+    buf.extend_from_slice(&maxp.max_zones.to_be_bytes());
+    buf.extend_from_slice(&maxp.max_twilight_points.to_be_bytes());
+    buf.extend_from_slice(&maxp.max_storage.to_be_bytes());
+    buf.extend_from_slice(&maxp.max_function_defs.to_be_bytes());
+    buf.extend_from_slice(&maxp.max_instruction_defs.to_be_bytes());
+    buf.extend_from_slice(&maxp.max_stack_elements.to_be_bytes());
+    buf.extend_from_slice(&maxp.max_size_of_instructions.to_be_bytes());
+    buf.extend_from_slice(&maxima.max_component_elements.to_be_bytes());
+    buf.extend_from_slice(&maxima.max_component_depth.to_be_bytes());
+    buf
+}
+
+struct GlyphMaxima {
+    max_points: u16,
+    max_contours: u16,
+    max_composite_points: u16,
+    max_composite_contours: u16,
+    max_component_elements: u16,
+    max_component_depth: u16,
+}
+
+/// Walk the subsetted glyph set to recompute every maxp maximum that
+/// depends on glyph contents: simple-glyph point/contour counts, the
+/// point/contour counts a composite glyph pulls in through its
+/// components, the widest top-level component list, and the deepest
+/// chain of composite-glyph references.
+fn compute_glyph_maxima(records: &[GlyfRecord]) -> GlyphMaxima {
+    let mut maxima = GlyphMaxima {
+        max_points: 0,
+        max_contours: 0,
+        max_composite_points: 0,
+        max_composite_contours: 0,
+        max_component_elements: 0,
+        max_component_depth: 0,
+    };
+
+    for record in records {
+        if let GlyfRecord::Present(glyph) = record {
+            match &glyph.data {
+                GlyphData::Simple(simple) => {
+                    let num_points: usize = simple.contours.iter().map(|c| c.len()).sum();
+                    maxima.max_points = maxima.max_points.max(num_points as u16);
+                    maxima.max_contours = maxima.max_contours.max(simple.contours.len() as u16);
+                }
+                GlyphData::Composite { glyphs, .. } => {
+                    let (points, contours) = simple_points_contours(records, glyphs, 1);
+                    maxima.max_composite_points = maxima.max_composite_points.max(points);
+                    maxima.max_composite_contours = maxima.max_composite_contours.max(contours);
+                    maxima.max_component_elements =
+                        maxima.max_component_elements.max(glyphs.len() as u16);
+                    maxima.max_component_depth = maxima
+                        .max_component_depth
+                        .max(component_depth(records, glyphs, 1));
+                }
+            }
+        }
+    }
+
+    maxima
+}
+
+/// Sum of simple-glyph points/contours reachable through a composite
+/// glyph's components, recursing through nested composites.
+fn simple_points_contours(
+    records: &[GlyfRecord],
+    glyphs: &[fontcode::tables::glyf::CompositeGlyphComponent],
+    depth: u16,
+) -> (u16, u16) {
+    if depth > 16 {
+        return (0, 0); // guard against malformed cyclic references
+    }
+    glyphs.iter().fold((0, 0), |(points, contours), component| {
+        match records.get(usize::from(component.glyph_index)) {
+            Some(GlyfRecord::Present(glyph)) => match &glyph.data {
+                GlyphData::Simple(simple) => {
+                    let num_points: u16 = simple.contours.iter().map(|c| c.len() as u16).sum();
+                    (points + num_points, contours + simple.contours.len() as u16)
+                }
+                GlyphData::Composite { glyphs: nested, .. } => {
+                    let (p, c) = simple_points_contours(records, nested, depth + 1);
+                    (points + p, contours + c)
+                }
+            },
+            _ => (points, contours),
+        }
+    })
+}
+
+fn component_depth(
+    records: &[GlyfRecord],
+    glyphs: &[fontcode::tables::glyf::CompositeGlyphComponent],
+    depth: u16,
+) -> u16 {
+    if depth > 16 {
+        return depth; // guard against malformed cyclic references
+    }
+    glyphs
+        .iter()
+        .map(
+            |component| match records.get(usize::from(component.glyph_index)) {
+                Some(GlyfRecord::Present(glyph)) => match &glyph.data {
+                    GlyphData::Composite { glyphs: nested, .. } => {
+                        component_depth(records, nested, depth + 1)
+                    }
+                    GlyphData::Simple(_) => depth,
+                },
+                _ => depth,
+            },
+        )
+        .max()
+        .unwrap_or(depth)
+}
+
+fn write_hmtx(hmtx: &[(u16, i16)]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(hmtx.len() * 4);
+    for &(advance_width, lsb) in hmtx {
+        buf.extend_from_slice(&advance_width.to_be_bytes());
+        buf.extend_from_slice(&lsb.to_be_bytes());
+    }
+    buf
+}
+
+/// Re-serialise the glyph table, choosing short (format 0) loca offsets
+/// when every offset fits, long (format 1) offsets otherwise.
+fn write_loca_and_glyf(records: &[GlyfRecord]) -> Result<(Vec<u8>, Vec<u8>, i16), Error> {
+    let mut glyf = Vec::new();
+    let mut offsets = vec![0u32];
+    for record in records {
+        if let GlyfRecord::Present(glyph) = record {
+            glyf.extend_from_slice(&write_glyph(glyph)?);
+            // glyf entries are padded to a 2-byte boundary
+            if glyf.len() % 2 != 0 {
+                glyf.push(0);
+            }
+        }
+        offsets.push(glyf.len() as u32);
+    }
+
+    let long_format = offsets.last().copied().unwrap_or(0) > u32::from(u16::MAX) * 2;
+    let mut loca = Vec::new();
+    if long_format {
+        for offset in &offsets {
+            loca.extend_from_slice(&offset.to_be_bytes());
+        }
+        Ok((loca, glyf, 1))
+    } else {
+        for offset in &offsets {
+            loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+        }
+        Ok((loca, glyf, 0))
+    }
+}
+
+fn write_glyph(glyph: &Glyph) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    match &glyph.data {
+        GlyphData::Simple(simple) => {
+            buf.extend_from_slice(&(simple.contours.len() as i16).to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.x_min.to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.y_min.to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.x_max.to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.y_max.to_be_bytes());
+            write_simple_glyph_body(&mut buf, simple);
+        }
+        GlyphData::Composite {
+            glyphs,
+            instructions,
+        } => {
+            buf.extend_from_slice(&(-1i16).to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.x_min.to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.y_min.to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.x_max.to_be_bytes());
+            buf.extend_from_slice(&glyph.bounding_box.y_max.to_be_bytes());
+            write_composite_glyph_body(&mut buf, glyphs, instructions);
+        }
+    }
+    Ok(buf)
+}
+
+fn write_simple_glyph_body(buf: &mut Vec<u8>, simple: &fontcode::tables::glyf::SimpleGlyph) {
+    let mut end_pt = 0u16;
+    for contour in &simple.contours {
+        end_pt += contour.len() as u16 - 1;
+        buf.extend_from_slice(&end_pt.to_be_bytes());
+        end_pt += 1;
+    }
+    buf.extend_from_slice(&(simple.instructions.len() as u16).to_be_bytes());
+    buf.extend_from_slice(&simple.instructions);
+
+    let points: Vec<_> = simple.contours.iter().flatten().collect();
+    for point in &points {
+        buf.push(if point.on_curve { 0x01 } else { 0x00 });
+    }
+    let mut prev_x = 0i16;
+    for point in &points {
+        buf.extend_from_slice(&(point.x - prev_x).to_be_bytes());
+        prev_x = point.x;
+    }
+    let mut prev_y = 0i16;
+    for point in &points {
+        buf.extend_from_slice(&(point.y - prev_y).to_be_bytes());
+        prev_y = point.y;
+    }
+}
+
+fn write_composite_glyph_body(
+    buf: &mut Vec<u8>,
+    glyphs: &[fontcode::tables::glyf::CompositeGlyphComponent],
+    instructions: &[u8],
+) {
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+    const ARGS_ARE_WORDS: u16 = 0x0001;
+
+    for (index, component) in glyphs.iter().enumerate() {
+        let is_last = index + 1 == glyphs.len();
+        let mut flags = component.flags | ARGS_ARE_WORDS;
+        if !is_last {
+            flags |= MORE_COMPONENTS;
+        } else if !instructions.is_empty() {
+            flags |= WE_HAVE_INSTRUCTIONS;
+        }
+        buf.extend_from_slice(&flags.to_be_bytes());
+        buf.extend_from_slice(&component.glyph_index.to_be_bytes());
+        buf.extend_from_slice(&component.argument1.to_be_bytes());
+        buf.extend_from_slice(&component.argument2.to_be_bytes());
+        if let Some(transform) = &component.transform {
+            for value in transform.as_f2dot14_words() {
+                buf.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+    }
+    if !instructions.is_empty() {
+        buf.extend_from_slice(&(instructions.len() as u16).to_be_bytes());
+        buf.extend_from_slice(instructions);
+    }
+}
+
+/// Emit a format 4 cmap subtable covering the BMP; if any surviving
+/// codepoint is outside it, also emit a format 12 subtable for the full
+/// range and point the Windows/Unicode UCS-4 encoding record at it.
+fn write_cmap(entries: &[(u32, u16)]) -> Vec<u8> {
+    let mut sorted = entries.to_vec();
+    sorted.sort_unstable_by_key(|&(ch, _)| ch);
+
+    let needs_format_12 = sorted.iter().any(|&(ch, _)| ch > 0xFFFF);
+    let bmp_entries: Vec<(u16, u16)> = sorted
+        .iter()
+        .filter(|&&(ch, _)| ch <= 0xFFFF)
+        .map(|&(ch, gid)| (ch as u16, gid))
+        .collect();
+
+    let format4 = write_cmap_format4(&bmp_entries);
+
+    let mut records = vec![(3u16, 1u16, 4u32)]; // Windows, Unicode BMP -> format 4
+    let mut subtables = vec![format4];
+    let mut offset = 4 + records.len() as u32 * 8;
+
+    if needs_format_12 {
+        let format12 = write_cmap_format12(&sorted);
+        records[0].2 = offset; // keep relative offsets consistent below
+        records.push((3, 10, 0)); // Windows, UCS-4 -> format 12, offset fixed below
+        subtables.push(format12);
+    }
+
+    // Recompute offsets now that we know the final subtable count/order.
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&0u16.to_be_bytes()); // version
+    buf.extend_from_slice(&(records.len() as u16).to_be_bytes()); // numTables
+    let header_len = 4 + records.len() * 8;
+    let mut running = header_len as u32;
+    let mut final_offsets = Vec::with_capacity(subtables.len());
+    for subtable in &subtables {
+        final_offsets.push(running);
+        running += subtable.len() as u32;
+    }
+    for (i, (platform_id, encoding_id, _)) in records.iter().enumerate() {
+        buf.extend_from_slice(&platform_id.to_be_bytes());
+        buf.extend_from_slice(&encoding_id.to_be_bytes());
+        buf.extend_from_slice(&final_offsets[i].to_be_bytes());
+    }
+    for subtable in subtables {
+        buf.extend_from_slice(&subtable);
+    }
+    let _ = offset; // superseded by final_offsets
+    buf
+}
+
+fn write_cmap_format4(entries: &[(u16, u16)]) -> Vec<u8> {
+    // Collapse into contiguous (start, end, start_gid) segments, terminated
+    // by the mandatory 0xFFFF end segment mapping to glyph 0 (idDelta 1).
+    let mut segments: Vec<(u16, u16, u16)> = Vec::new();
+    for &(ch, gid) in entries {
+        if let Some(last) = segments.last_mut() {
+            let (start, end, start_gid) = *last;
+            if ch == end + 1 && gid == start_gid + (end - start) + 1 {
+                last.1 = ch;
+                continue;
+            }
+        }
+        segments.push((ch, ch, gid));
+    }
+    segments.push((0xFFFF, 0xFFFF, 0));
+
+    let seg_count = segments.len() as u16;
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&4u16.to_be_bytes()); // format
+    buf.extend_from_slice(&0u16.to_be_bytes()); // length placeholder, patched below
+    buf.extend_from_slice(&0u16.to_be_bytes()); // language
+    buf.extend_from_slice(&(seg_count * 2).to_be_bytes());
+    // searchRange = 2 * 2^floor(log2(segCount)); floor_pow2 below is that
+    // 2^floor(log2(segCount)) term.
+    let entry_selector = 15 - seg_count.leading_zeros() as u16;
+    let floor_pow2 = 1u16 << entry_selector;
+    let search_range = floor_pow2 * 2;
+    buf.extend_from_slice(&search_range.to_be_bytes());
+    buf.extend_from_slice(&entry_selector.to_be_bytes());
+    buf.extend_from_slice(&(seg_count * 2 - search_range).to_be_bytes());
+
+    for &(_, end, _) in &segments {
+        buf.extend_from_slice(&end.to_be_bytes());
+    }
+    buf.extend_from_slice(&0u16.to_be_bytes()); // reservedPad
+    for &(start, _, _) in &segments {
+        buf.extend_from_slice(&start.to_be_bytes());
+    }
+    for &(start, _, gid) in &segments {
+        let id_delta = gid.wrapping_sub(start);
+        buf.extend_from_slice(&id_delta.to_be_bytes());
+    }
+    for _ in &segments {
+        buf.extend_from_slice(&0u16.to_be_bytes()); // idRangeOffset: 0, use idDelta
+    }
+
+    let length = buf.len() as u16;
+    buf[2..4].copy_from_slice(&length.to_be_bytes());
+    buf
+}
+
+fn write_cmap_format12(entries: &[(u32, u16)]) -> Vec<u8> {
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for &(ch, gid) in entries {
+        let gid = u32::from(gid);
+        if let Some(last) = groups.last_mut() {
+            let (start, end, start_gid) = *last;
+            if ch == end + 1 && gid == start_gid + (end - start) + 1 {
+                last.1 = ch;
+                continue;
+            }
+        }
+        groups.push((ch, ch, gid));
+    }
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&12u16.to_be_bytes()); // format
+    buf.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    buf.extend_from_slice(&0u32.to_be_bytes()); // length placeholder, patched below
+    buf.extend_from_slice(&0u32.to_be_bytes()); // language
+    buf.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+    for (start, end, start_gid) in groups {
+        buf.extend_from_slice(&start.to_be_bytes());
+        buf.extend_from_slice(&end.to_be_bytes());
+        buf.extend_from_slice(&start_gid.to_be_bytes());
+    }
+
+    let length = buf.len() as u32;
+    buf[4..8].copy_from_slice(&length.to_be_bytes());
+    buf
+}
+
+/// Lay out the table directory, pad each table body to a 4-byte boundary,
+/// and compute every table checksum plus the head checkAdjustment.
+fn assemble_sfnt(sfnt_version: u32, tables: Vec<(u32, Vec<u8>)>) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let mut search_range = 16u16;
+    let mut entry_selector = 0u16;
+    while search_range * 2 <= num_tables * 16 {
+        search_range *= 2;
+        entry_selector += 1;
+    }
+    let range_shift = num_tables * 16 - search_range;
+
+    let header_len = 12 + usize::from(num_tables) * 16;
+    let mut directory = Vec::with_capacity(usize::from(num_tables) * 16);
+    let mut body = Vec::new();
+    let mut offset = header_len as u32;
+    let mut head_checksum_offset = None;
+
+    for (tag, data) in &tables {
+        let checksum = table_checksum(data);
+        if *tag == tag::HEAD {
+            head_checksum_offset = Some(body.len() + header_len);
+        }
+        directory.extend_from_slice(&tag.to_be_bytes());
+        directory.extend_from_slice(&checksum.to_be_bytes());
+        directory.extend_from_slice(&offset.to_be_bytes());
+        directory.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+        body.extend_from_slice(data);
+        let padding = (4 - data.len() % 4) % 4;
+        body.extend(std::iter::repeat(0u8).take(padding));
+        offset += (data.len() + padding) as u32;
+    }
+
+    let mut font = Vec::with_capacity(header_len + body.len());
+    font.extend_from_slice(&sfnt_version.to_be_bytes());
+    font.extend_from_slice(&num_tables.to_be_bytes());
+    font.extend_from_slice(&search_range.to_be_bytes());
+    font.extend_from_slice(&entry_selector.to_be_bytes());
+    font.extend_from_slice(&range_shift.to_be_bytes());
+    font.extend_from_slice(&directory);
+    font.extend_from_slice(&body);
+
+    // head.checkSumAdjustment = 0xB1B0AFBA - checksum of the whole font
+    // (header, directory and every table, with checkSumAdjustment still 0)
+    let font_checksum = table_checksum(&font);
+    let check_sum_adjustment = 0xB1B0_AFBAu32.wrapping_sub(font_checksum);
+    if let Some(head_offset) = head_checksum_offset {
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&check_sum_adjustment.to_be_bytes());
+    }
+
+    font
+}
+
+fn table_checksum(data: &[u8]) -> u32 {
+    let mut sum = 0u32;
+    let mut chunks = data.chunks(4);
+    for chunk in &mut chunks {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum = sum.wrapping_add(u32::from_be_bytes(word));
+    }
+    sum
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,209 @@
+use getopts::Options;
+
+use fontcode::error::ParseError;
+use fontcode::read::ReadScope;
+use fontcode::tables::{OffsetTable, OpenTypeFile, OpenTypeFont};
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    Message(&'static str),
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optopt(
+        "o",
+        "output",
+        "write the WOFF file to FILE (default <input>.woff)",
+        "FILE",
+    );
+    opts.optopt(
+        "m",
+        "metadata",
+        "embed this extended metadata XML file",
+        "FILE",
+    );
+    opts.optflag("h", "help", "print this help menu");
+
+    let matches = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => panic!(f.to_string()),
+    };
+
+    if matches.opt_present("h") || matches.free.is_empty() {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+
+    let filename = &matches.free[0];
+    let output = matches
+        .opt_str("o")
+        .unwrap_or_else(|| format!("{}.woff", filename));
+    let metadata = matches.opt_str("m").map(read_file).transpose()?;
+
+    let buffer = read_file(filename)?;
+    let fontfile = ReadScope::new(&buffer).read::<OpenTypeFile>()?;
+    let ttf = match fontfile.font {
+        OpenTypeFont::Single(ttf) => ttf,
+        OpenTypeFont::Collection(_) => return Err(Error::Message("TTC not supported")),
+    };
+
+    let woff_data = encode_woff(fontfile.scope, &ttf, metadata.as_deref())?;
+
+    let mut out = File::create(&output)?;
+    out.write_all(&woff_data)?;
+    println!("wrote {} ({} bytes)", output, woff_data.len());
+
+    Ok(())
+}
+
+fn print_usage(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options] FONTFILE ", program);
+    eprint!("{}", opts.usage(&brief));
+}
+
+fn read_file(path: impl AsRef<std::path::Path>) -> Result<Vec<u8>, io::Error> {
+    let mut file = File::open(path)?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Compress an sfnt into a WOFF 1.0 container: each table body is
+/// zlib-compressed (stored uncompressed when that doesn't shrink it, as
+/// the spec requires), the directory is sorted by tag as the spec
+/// requires, and each table is padded to a 4-byte boundary in the
+/// compressed data block.
+fn encode_woff(
+    scope: ReadScope,
+    ttf: &OffsetTable,
+    metadata: Option<&[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let total_sfnt_size = sfnt_size(ttf);
+
+    struct Entry {
+        tag: u32,
+        orig_checksum: u32,
+        orig_length: u32,
+        comp_data: Vec<u8>,
+    }
+
+    let mut entries = Vec::with_capacity(ttf.table_records.len());
+    for table_record in &ttf.table_records {
+        let table = table_record.read_table(scope)?;
+        let data = table.data();
+        let compressed = zlib_compress(data)?;
+        let comp_data = if compressed.len() < data.len() {
+            compressed
+        } else {
+            data.to_vec()
+        };
+        entries.push(Entry {
+            tag: table_record.table_tag,
+            orig_checksum: table_record.checksum,
+            orig_length: data.len() as u32,
+            comp_data,
+        });
+    }
+    // The WOFF directory must be sorted by tag regardless of the order
+    // the source sfnt's table directory used.
+    entries.sort_by_key(|entry| entry.tag);
+
+    let header_len = 44;
+    let directory_len = entries.len() * 20;
+    let mut table_data = Vec::new();
+    let mut directory = Vec::with_capacity(directory_len);
+    let mut offset = (header_len + directory_len) as u32;
+
+    for entry in &entries {
+        directory.extend_from_slice(&entry.tag.to_be_bytes());
+        directory.extend_from_slice(&offset.to_be_bytes());
+        directory.extend_from_slice(&(entry.comp_data.len() as u32).to_be_bytes());
+        directory.extend_from_slice(&entry.orig_length.to_be_bytes());
+        directory.extend_from_slice(&entry.orig_checksum.to_be_bytes());
+
+        table_data.extend_from_slice(&entry.comp_data);
+        let padding = (4 - table_data.len() % 4) % 4;
+        table_data.extend(std::iter::repeat(0u8).take(padding));
+        offset += entry.comp_data.len() as u32 + padding as u32;
+    }
+
+    let (meta_offset, meta_length, meta_orig_length, meta_bytes) = match metadata {
+        Some(xml) => {
+            let compressed = zlib_compress(xml)?;
+            let meta_offset = offset;
+            let mut padded = compressed.clone();
+            let padding = (4 - padded.len() % 4) % 4;
+            padded.extend(std::iter::repeat(0u8).take(padding));
+            (
+                meta_offset,
+                compressed.len() as u32,
+                xml.len() as u32,
+                padded,
+            )
+        }
+        None => (0, 0, 0, Vec::new()),
+    };
+    let total_length = offset + meta_bytes.len() as u32;
+
+    let mut woff = Vec::with_capacity(total_length as usize);
+    woff.extend_from_slice(b"wOFF");
+    woff.extend_from_slice(&ttf.sfnt_version.to_be_bytes());
+    woff.extend_from_slice(&total_length.to_be_bytes());
+    woff.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+    woff.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    woff.extend_from_slice(&total_sfnt_size.to_be_bytes());
+    woff.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+    woff.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+    woff.extend_from_slice(&meta_offset.to_be_bytes());
+    woff.extend_from_slice(&meta_length.to_be_bytes());
+    woff.extend_from_slice(&meta_orig_length.to_be_bytes());
+    woff.extend_from_slice(&0u32.to_be_bytes()); // privOffset
+    woff.extend_from_slice(&0u32.to_be_bytes()); // privLength
+
+    woff.extend_from_slice(&directory);
+    woff.extend_from_slice(&table_data);
+    woff.extend_from_slice(&meta_bytes);
+
+    Ok(woff)
+}
+
+/// `totalSfntSize`: the reconstructed TTF/OTF's size, with every table
+/// padded to a 4-byte boundary, as the WOFF spec requires.
+fn sfnt_size(ttf: &OffsetTable) -> u32 {
+    let header = 12 + ttf.table_records.len() as u32 * 16;
+    ttf.table_records.iter().fold(header, |size, table_record| {
+        let padded = (table_record.length + 3) & !3;
+        size + padded
+    })
+}
+
+fn zlib_compress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}